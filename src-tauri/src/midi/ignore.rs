@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+
+/// Which high-rate message categories an input connection should have
+/// midir filter out before they ever reach the callback, mapping to
+/// midir's `Ignore::{Sysex,Time,ActiveSense}` flags.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+pub struct IgnoreFlags {
+    #[serde(default)]
+    pub sysex: bool,
+    #[serde(default)]
+    pub time: bool,
+    #[serde(default)]
+    pub active_sense: bool,
+}
+
+impl IgnoreFlags {
+    pub fn to_midir(self) -> midir::Ignore {
+        match (self.sysex, self.time, self.active_sense) {
+            (false, false, false) => midir::Ignore::None,
+            (true, false, false) => midir::Ignore::Sysex,
+            (false, true, false) => midir::Ignore::Time,
+            (false, false, true) => midir::Ignore::ActiveSense,
+            (true, true, false) => midir::Ignore::SysexAndTime,
+            (true, false, true) => midir::Ignore::SysexAndActiveSense,
+            (false, true, true) => midir::Ignore::TimeAndActiveSense,
+            (true, true, true) => midir::Ignore::All,
+        }
+    }
+}
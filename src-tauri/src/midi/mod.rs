@@ -0,0 +1,236 @@
+mod ignore;
+mod parser;
+mod playback;
+mod recording;
+mod routing;
+mod session;
+mod sysex;
+mod virtual_ports;
+mod watcher;
+
+pub use ignore::IgnoreFlags;
+
+use parser::{MidiEvent, RunningStatusParser};
+use routing::Route;
+use serde::{Deserialize, Serialize};
+use session::SESSION;
+use tauri::Emitter;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MidiDeviceInfo {
+    id: usize,
+    name: String,
+    kind: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MidiMessage {
+    connection_id: String,
+    timestamp_ms: u128,
+    data: Vec<u8>,
+    events: Vec<MidiEvent>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MidiSnapshot {
+    inputs: Vec<MidiDeviceInfo>,
+    outputs: Vec<MidiDeviceInfo>,
+}
+
+#[tauri::command]
+pub fn midi_list_inputs() -> Result<Vec<MidiDeviceInfo>, String> {
+    let mut out: Vec<MidiDeviceInfo> = Vec::new();
+    let midi_in = midir::MidiInput::new("wc-midi-in").map_err(|e| e.to_string())?;
+    for (i, port) in midi_in.ports().iter().enumerate() {
+        let name = midi_in.port_name(port).unwrap_or_else(|_| "Unknown".to_string());
+        out.push(MidiDeviceInfo { id: i, name, kind: "input".into() });
+    }
+    Ok(out)
+}
+
+#[tauri::command]
+pub fn midi_list_outputs() -> Result<Vec<MidiDeviceInfo>, String> {
+    let mut out: Vec<MidiDeviceInfo> = Vec::new();
+    let midi_out = midir::MidiOutput::new("wc-midi-out").map_err(|e| e.to_string())?;
+    for (i, port) in midi_out.ports().iter().enumerate() {
+        let name = midi_out.port_name(port).unwrap_or_else(|_| "Unknown".to_string());
+        out.push(MidiDeviceInfo { id: i, name, kind: "output".into() });
+    }
+    Ok(out)
+}
+
+/// Builds the per-connection callback midir invokes on every incoming
+/// message: decode it and emit `midi://message`. Shared by physical port
+/// connections and virtual ports so both behave identically to callers.
+fn input_callback(
+    app: tauri::AppHandle,
+    connection_id: String,
+) -> impl FnMut(u64, &[u8], &mut ()) + Send + 'static {
+    let mut decoder = RunningStatusParser::new();
+    move |_, message, _| {
+        let events = decoder.parse(message);
+        // Routes are evaluated before the emit so thru forwarding isn't
+        // gated on the frontend receiving and acting on the event.
+        routing::forward(&connection_id, &events);
+
+        let timestamp_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis();
+        recording::capture(timestamp_ms, message);
+
+        let payload = MidiMessage { connection_id: connection_id.clone(), timestamp_ms, events, data: message.to_vec() };
+        let _ = app.emit("midi://message", &payload);
+    }
+}
+
+/// Opens an input port and registers it under `connection_id`, replacing
+/// any existing connection with that same id. Also used internally by the
+/// hot-plug watcher to reconnect a device that disappeared and came back.
+fn open_input(
+    app: tauri::AppHandle,
+    connection_id: String,
+    input_index: usize,
+    ignore: IgnoreFlags,
+) -> Result<(), String> {
+    let mut midi_in = midir::MidiInput::new("wc-midi-in").map_err(|e| e.to_string())?;
+    midi_in.ignore(ignore.to_midir());
+    let ports = midi_in.ports();
+    let port = ports.get(input_index).ok_or_else(|| "Invalid input index".to_string())?;
+    let name = midi_in.port_name(port).unwrap_or_else(|_| "Unknown".to_string());
+
+    {
+        let mut inputs = SESSION.inputs.lock().map_err(|_| "Lock poisoned")?;
+        inputs.remove(&connection_id);
+        let mut virtual_inputs = SESSION.virtual_inputs.lock().map_err(|_| "Lock poisoned")?;
+        virtual_inputs.remove(&connection_id);
+    }
+
+    let conn = midi_in
+        .connect(port, "wc-midi-in-conn", input_callback(app, connection_id.clone()), ())
+        .map_err(|e| e.to_string())?;
+
+    let mut inputs = SESSION.inputs.lock().map_err(|_| "Lock poisoned")?;
+    inputs.insert(connection_id.clone(), conn);
+    let mut names = SESSION.input_names.lock().map_err(|_| "Lock poisoned")?;
+    names.insert(connection_id.clone(), name);
+    let mut ignores = SESSION.input_ignore.lock().map_err(|_| "Lock poisoned")?;
+    ignores.insert(connection_id, ignore);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn midi_open_input(
+    app: tauri::AppHandle,
+    connection_id: String,
+    input_index: usize,
+    ignore: Option<IgnoreFlags>,
+) -> Result<(), String> {
+    open_input(app, connection_id, input_index, ignore.unwrap_or_default())
+}
+
+/// Opens an output port and registers it under `connection_id`, replacing
+/// any existing connection with that same id.
+fn open_output(connection_id: String, output_index: usize) -> Result<(), String> {
+    let midi_out = midir::MidiOutput::new("wc-midi-out").map_err(|e| e.to_string())?;
+    let ports = midi_out.ports();
+    let port = ports.get(output_index).ok_or_else(|| "Invalid output index".to_string())?;
+    let name = midi_out.port_name(port).unwrap_or_else(|_| "Unknown".to_string());
+
+    {
+        let mut outputs = SESSION.outputs.lock().map_err(|_| "Lock poisoned")?;
+        outputs.remove(&connection_id);
+        let mut virtual_outputs = SESSION.virtual_outputs.lock().map_err(|_| "Lock poisoned")?;
+        virtual_outputs.remove(&connection_id);
+    }
+
+    let conn = midi_out.connect(port, "wc-midi-out-conn").map_err(|e| e.to_string())?;
+    let mut outputs = SESSION.outputs.lock().map_err(|_| "Lock poisoned")?;
+    outputs.insert(connection_id.clone(), conn);
+    let mut names = SESSION.output_names.lock().map_err(|_| "Lock poisoned")?;
+    names.insert(connection_id, name);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn midi_open_output(connection_id: String, output_index: usize) -> Result<(), String> {
+    open_output(connection_id, output_index)
+}
+
+#[tauri::command]
+pub fn midi_send(connection_id: String, bytes: Vec<u8>) -> Result<(), String> {
+    let mut outputs = SESSION.outputs.lock().map_err(|_| "Lock poisoned")?;
+    match outputs.get_mut(&connection_id) {
+        Some(conn) => conn.send(&bytes).map_err(|e| e.to_string()),
+        None => Err(format!("Output '{connection_id}' not connected")),
+    }
+}
+
+/// Sends a validated SysEx dump to `connection_id` in one message.
+#[tauri::command]
+pub fn midi_send_sysex(connection_id: String, bytes: Vec<u8>) -> Result<(), String> {
+    sysex::send_sysex(&connection_id, &bytes)
+}
+
+#[tauri::command]
+pub fn midi_refresh() -> Result<MidiSnapshot, String> {
+    Ok(MidiSnapshot { inputs: midi_list_inputs()?, outputs: midi_list_outputs()? })
+}
+
+/// Publishes CueBoard itself as a selectable MIDI source that other apps
+/// (DAWs, lighting software) can connect to directly, no loopback driver
+/// needed. Unsupported on Windows, which has no virtual-port API.
+#[tauri::command]
+pub fn midi_create_virtual_input(
+    app: tauri::AppHandle,
+    connection_id: String,
+    name: String,
+) -> Result<(), String> {
+    virtual_ports::create_virtual_input(app, connection_id, name)
+}
+
+/// Publishes CueBoard itself as a selectable MIDI destination. Unsupported
+/// on Windows, which has no virtual-port API.
+#[tauri::command]
+pub fn midi_create_virtual_output(connection_id: String, name: String) -> Result<(), String> {
+    virtual_ports::create_virtual_output(connection_id, name)
+}
+
+/// Replaces the whole routing table atomically so the frontend can
+/// reconfigure CueBoard's patchbay in one call instead of diffing routes
+/// in and out one at a time.
+#[tauri::command]
+pub fn midi_set_routes(routes: Vec<Route>) {
+    routing::set_routes(routes);
+}
+
+/// Starts the background hot-plug watcher. Called once from `run()`.
+pub fn start_watcher(app: tauri::AppHandle) {
+    watcher::start(app);
+}
+
+/// Arms the recorder: every incoming message on every open input is
+/// captured from this point on, discarding anything captured previously.
+#[tauri::command]
+pub fn midi_arm_recording() {
+    recording::arm();
+}
+
+#[tauri::command]
+pub fn midi_stop_recording() {
+    recording::disarm();
+}
+
+/// Exports everything captured while armed as a Type-1 Standard MIDI
+/// File. Defaults to 480 PPQ at 120 BPM when not specified.
+#[tauri::command]
+pub fn midi_export_recording(path: String, ppq: Option<u16>, tempo_bpm: Option<f64>) -> Result<(), String> {
+    recording::export_recording(&path, ppq.unwrap_or(480), tempo_bpm.unwrap_or(120.0))
+}
+
+/// Parses a Standard MIDI File and streams its events to `output_id` on a
+/// background timer thread, for reproducible MIDI cue playback.
+#[tauri::command]
+pub fn midi_play_file(path: String, output_id: String) -> Result<(), String> {
+    playback::play_file(&path, output_id)
+}
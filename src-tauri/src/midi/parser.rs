@@ -0,0 +1,238 @@
+use serde::{Deserialize, Serialize};
+
+/// A decoded MIDI event. Mirrors the channel-voice and system message
+/// taxonomy from the MIDI 1.0 spec so the frontend can bind cues to
+/// semantic events (`NoteOn`, `ControlChange`, ...) instead of re-parsing
+/// magic numbers out of the raw bytes.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type")]
+pub enum MidiEvent {
+    NoteOn { channel: u8, key: u8, velocity: u8 },
+    NoteOff { channel: u8, key: u8, velocity: u8 },
+    ControlChange { channel: u8, controller: u8, value: u8 },
+    ProgramChange { channel: u8, program: u8 },
+    PitchBend { channel: u8, value: i16 },
+    ChannelPressure { channel: u8, pressure: u8 },
+    PolyPressure { channel: u8, key: u8, pressure: u8 },
+    SystemRealtime { status: u8 },
+    SystemCommon { status: u8, data: Vec<u8> },
+}
+
+/// Decodes raw MIDI bytes into [`MidiEvent`]s one byte at a time, the way
+/// a real MIDI stream must be read: a status byte can be omitted and the
+/// previous channel-voice status reused ("running status"), and a
+/// system-realtime byte can interrupt another message mid-flight without
+/// disturbing it. One parser is kept per open input connection so
+/// unrelated streams don't bleed into each other's running status.
+#[derive(Default)]
+pub struct RunningStatusParser {
+    /// Status byte of the channel-voice message currently being
+    /// assembled, kept alive across messages for running status.
+    last_status: Option<u8>,
+    /// Status byte of whatever message is presently collecting data
+    /// bytes (channel-voice or system common); cleared once it completes.
+    current: Option<u8>,
+    data: Vec<u8>,
+    /// Bytes collected so far for an in-progress SysEx dump, starting
+    /// with the leading 0xF0.
+    sysex: Option<Vec<u8>>,
+}
+
+impl RunningStatusParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn parse(&mut self, bytes: &[u8]) -> Vec<MidiEvent> {
+        let mut events = Vec::new();
+
+        for &byte in bytes {
+            // System realtime bytes can interrupt any other message in
+            // flight and never affect running status or data collection.
+            if byte >= 0xF8 {
+                events.push(MidiEvent::SystemRealtime { status: byte });
+                continue;
+            }
+
+            if byte & 0x80 != 0 {
+                if byte == 0xF7 {
+                    if let Some(mut buf) = self.sysex.take() {
+                        buf.push(byte);
+                        events.push(MidiEvent::SystemCommon { status: 0xF0, data: buf });
+                    }
+                    self.current = None;
+                    self.data.clear();
+                    self.last_status = None;
+                    continue;
+                }
+
+                self.sysex = None;
+                self.data.clear();
+
+                if byte == 0xF0 {
+                    self.sysex = Some(vec![byte]);
+                    self.current = None;
+                    self.last_status = None;
+                } else if byte >= 0xF1 {
+                    // System common clears running status. Some of these
+                    // (e.g. Tune Request 0xF6) carry no data bytes at all,
+                    // so they're already complete and must be emitted here
+                    // rather than left waiting for data that never comes.
+                    self.last_status = None;
+                    if required_len(byte) == 0 {
+                        self.current = None;
+                        events.push(MidiEvent::SystemCommon { status: byte, data: Vec::new() });
+                    } else {
+                        self.current = Some(byte);
+                    }
+                } else {
+                    self.current = Some(byte);
+                    self.last_status = Some(byte);
+                }
+                continue;
+            }
+
+            // Data byte.
+            if let Some(buf) = self.sysex.as_mut() {
+                buf.push(byte);
+                continue;
+            }
+
+            let status = match self.current.or(self.last_status) {
+                Some(s) => s,
+                None => continue, // stray data byte with nothing to attach it to
+            };
+            if self.current.is_none() {
+                // No explicit status byte preceded this one: running status.
+                self.current = Some(status);
+                self.data.clear();
+            }
+            self.data.push(byte);
+
+            if self.data.len() == required_len(status) {
+                events.push(build_event(status, &self.data));
+                self.current = None;
+                self.data.clear();
+            }
+        }
+
+        events
+    }
+}
+
+fn build_event(status: u8, data: &[u8]) -> MidiEvent {
+    if status >= 0xF0 {
+        return MidiEvent::SystemCommon { status, data: data.to_vec() };
+    }
+
+    let channel = status & 0x0F;
+    match status & 0xF0 {
+        0x80 => MidiEvent::NoteOff { channel, key: data[0], velocity: data[1] },
+        // A Note On with velocity 0 is a Note Off in disguise, used so
+        // devices can keep sending running-status Note Ons.
+        0x90 if data[1] == 0 => MidiEvent::NoteOff { channel, key: data[0], velocity: 0 },
+        0x90 => MidiEvent::NoteOn { channel, key: data[0], velocity: data[1] },
+        0xA0 => MidiEvent::PolyPressure { channel, key: data[0], pressure: data[1] },
+        0xB0 => MidiEvent::ControlChange { channel, controller: data[0], value: data[1] },
+        0xC0 => MidiEvent::ProgramChange { channel, program: data[0] },
+        0xD0 => MidiEvent::ChannelPressure { channel, pressure: data[0] },
+        // 14-bit value reassembled LSB (data[0]) then MSB (data[1]),
+        // centered on 0 from the spec's 0..16383 range.
+        0xE0 => MidiEvent::PitchBend { channel, value: (((data[1] as i16) << 7) | data[0] as i16) - 8192 },
+        _ => unreachable!("channel is masked out of status by 0xF0"),
+    }
+}
+
+fn required_len(status: u8) -> usize {
+    if status < 0xF0 {
+        channel_voice_data_len(status & 0xF0)
+    } else {
+        system_common_data_len(status)
+    }
+}
+
+fn channel_voice_data_len(kind: u8) -> usize {
+    match kind {
+        0xC0 | 0xD0 => 1,
+        _ => 2,
+    }
+}
+
+fn system_common_data_len(status: u8) -> usize {
+    match status {
+        0xF1 | 0xF3 => 1,
+        0xF2 => 2,
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn note_on_zero_velocity_is_note_off() {
+        let mut parser = RunningStatusParser::new();
+        let events = parser.parse(&[0x90, 0x40, 0x00]);
+        assert_eq!(events, vec![MidiEvent::NoteOff { channel: 0, key: 0x40, velocity: 0 }]);
+    }
+
+    #[test]
+    fn running_status_reuses_previous_channel_voice_message() {
+        let mut parser = RunningStatusParser::new();
+        let events = parser.parse(&[0x90, 0x40, 0x7F, 0x41, 0x7F]);
+        assert_eq!(
+            events,
+            vec![
+                MidiEvent::NoteOn { channel: 0, key: 0x40, velocity: 0x7F },
+                MidiEvent::NoteOn { channel: 0, key: 0x41, velocity: 0x7F },
+            ]
+        );
+    }
+
+    #[test]
+    fn pitch_bend_reassembles_14_bit_value() {
+        let mut parser = RunningStatusParser::new();
+        // LSB=0x00, MSB=0x40 -> 0x2000 (8192), centered value of 0.
+        let events = parser.parse(&[0xE3, 0x00, 0x40]);
+        assert_eq!(events, vec![MidiEvent::PitchBend { channel: 3, value: 0 }]);
+    }
+
+    #[test]
+    fn system_realtime_interrupts_without_corrupting_in_flight_message() {
+        let mut parser = RunningStatusParser::new();
+        let events = parser.parse(&[0x90, 0x40, 0xF8, 0x7F]);
+        assert_eq!(
+            events,
+            vec![
+                MidiEvent::SystemRealtime { status: 0xF8 },
+                MidiEvent::NoteOn { channel: 0, key: 0x40, velocity: 0x7F },
+            ]
+        );
+    }
+
+    #[test]
+    fn zero_data_system_common_is_emitted_immediately() {
+        let mut parser = RunningStatusParser::new();
+        // Tune Request (0xF6) takes no data bytes, so it must complete as
+        // soon as its status byte arrives, not wait for a following byte.
+        let events = parser.parse(&[0xF6, 0x90, 0x40, 0x7F]);
+        assert_eq!(
+            events,
+            vec![
+                MidiEvent::SystemCommon { status: 0xF6, data: vec![] },
+                MidiEvent::NoteOn { channel: 0, key: 0x40, velocity: 0x7F },
+            ]
+        );
+    }
+
+    #[test]
+    fn sysex_is_collected_until_eox() {
+        let mut parser = RunningStatusParser::new();
+        let events = parser.parse(&[0xF0, 0x7E, 0x00, 0xF7]);
+        assert_eq!(
+            events,
+            vec![MidiEvent::SystemCommon { status: 0xF0, data: vec![0xF0, 0x7E, 0x00, 0xF7] }]
+        );
+    }
+}
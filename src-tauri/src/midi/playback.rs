@@ -0,0 +1,207 @@
+use super::session::SESSION;
+use std::thread;
+use std::time::{Duration, Instant};
+
+struct ScheduledEvent {
+    tick: u64,
+    data: Vec<u8>,
+}
+
+/// Parses a Standard MIDI File back into `(division, tracks)`, where each
+/// track is its still-encoded event stream (delta time + status/data
+/// bytes), ready for [`schedule`] to decode.
+fn parse_smf(bytes: &[u8]) -> Result<(u16, Vec<Vec<u8>>), String> {
+    if bytes.len() < 14 || &bytes[0..4] != b"MThd" {
+        return Err("Not a Standard MIDI File".into());
+    }
+    let header_len = u32::from_be_bytes(bytes[4..8].try_into().unwrap()) as usize;
+    let n_tracks = u16::from_be_bytes(bytes[10..12].try_into().unwrap());
+    let division = u16::from_be_bytes(bytes[12..14].try_into().unwrap());
+    if division & 0x8000 != 0 {
+        return Err("SMPTE time division is not supported".into());
+    }
+
+    let mut pos = 8usize.checked_add(header_len).ok_or("Malformed header chunk")?;
+    let mut tracks = Vec::with_capacity(n_tracks as usize);
+    for _ in 0..n_tracks {
+        if bytes.len() < pos + 8 || &bytes[pos..pos + 4] != b"MTrk" {
+            return Err("Malformed track chunk".into());
+        }
+        let len = u32::from_be_bytes(bytes[pos + 4..pos + 8].try_into().unwrap()) as usize;
+        let start = pos + 8;
+        let end = start.checked_add(len).ok_or("Malformed track chunk")?;
+        if end > bytes.len() {
+            return Err("Truncated track chunk".into());
+        }
+        tracks.push(bytes[start..end].to_vec());
+        pos = end;
+    }
+
+    Ok((division, tracks))
+}
+
+fn read_vlq(bytes: &[u8]) -> Result<(u32, usize), String> {
+    let mut value: u32 = 0;
+    let mut i = 0;
+    loop {
+        let byte = *bytes.get(i).ok_or("Truncated variable-length quantity")?;
+        value = (value << 7) | (byte & 0x7F) as u32;
+        i += 1;
+        if byte & 0x80 == 0 {
+            break;
+        }
+    }
+    Ok((value, i))
+}
+
+fn channel_voice_data_len(status: u8) -> usize {
+    match status & 0xF0 {
+        0xC0 | 0xD0 => 1,
+        _ => 2,
+    }
+}
+
+/// `&track[pos..pos+len]`, bounds-checked: a truncated or crafted `.smf`
+/// must produce an `Err`, not a panic, from `midi_play_file`.
+fn slice_at(track: &[u8], pos: usize, len: usize) -> Result<&[u8], String> {
+    let end = pos.checked_add(len).ok_or("Truncated track")?;
+    track.get(pos..end).ok_or_else(|| "Truncated track".to_string())
+}
+
+/// Walks every track's delta-time-encoded events, converts them to
+/// absolute ticks, collects the tempo from any `FF 51 03` meta event, and
+/// returns the sendable events (SysEx included, meta events dropped) in
+/// tick order ready to stream out.
+fn schedule(tracks: &[Vec<u8>]) -> Result<(u32, Vec<ScheduledEvent>), String> {
+    let mut tempo_usec_per_quarter: u32 = 500_000; // default 120 BPM
+    let mut events = Vec::new();
+
+    for track in tracks {
+        let mut pos = 0;
+        let mut tick: u64 = 0;
+        let mut running_status: Option<u8> = None;
+
+        while pos < track.len() {
+            let (delta, used) = read_vlq(&track[pos..])?;
+            pos += used;
+            tick += delta as u64;
+
+            let status = *track.get(pos).ok_or("Truncated track: missing status byte")?;
+
+            if status == 0xFF {
+                pos += 1;
+                let meta_type = *track.get(pos).ok_or("Truncated meta event")?;
+                pos += 1;
+                let (len, used) = read_vlq(&track[pos..])?;
+                pos += used;
+                let len = len as usize;
+                if meta_type == 0x51 && len == 3 {
+                    let payload = slice_at(track, pos, 3)?;
+                    tempo_usec_per_quarter =
+                        ((payload[0] as u32) << 16) | ((payload[1] as u32) << 8) | payload[2] as u32;
+                }
+                pos = pos.checked_add(len).ok_or("Truncated meta event")?;
+            } else if status == 0xF0 || status == 0xF7 {
+                pos += 1;
+                let (len, used) = read_vlq(&track[pos..])?;
+                pos += used;
+                let len = len as usize;
+                let payload = slice_at(track, pos, len)?;
+                // SMF strips the leading F0 of a SysEx event into the
+                // status byte and re-expresses it via the length VLQ; put
+                // it back so the bytes sent to the output are a complete,
+                // self-framed SysEx message. F7 escape/continuation
+                // events carry raw bytes with no status to restore.
+                let data = if status == 0xF0 {
+                    let mut data = Vec::with_capacity(1 + len);
+                    data.push(0xF0);
+                    data.extend_from_slice(payload);
+                    data
+                } else {
+                    payload.to_vec()
+                };
+                events.push(ScheduledEvent { tick, data });
+                pos += len;
+                running_status = None;
+            } else {
+                let event_status = if status & 0x80 != 0 {
+                    pos += 1;
+                    status
+                } else {
+                    running_status.ok_or("Data byte with no running status")?
+                };
+                running_status = Some(event_status);
+                let len = channel_voice_data_len(event_status);
+                let payload = slice_at(track, pos, len)?;
+                let mut data = Vec::with_capacity(1 + len);
+                data.push(event_status);
+                data.extend_from_slice(payload);
+                pos += len;
+                events.push(ScheduledEvent { tick, data });
+            }
+        }
+    }
+
+    events.sort_by_key(|e| e.tick);
+    Ok((tempo_usec_per_quarter, events))
+}
+
+/// Parses `path` and streams its events to `output_id` on a timer thread,
+/// converting delta ticks to wall-clock time using the file's tempo.
+pub fn play_file(path: &str, output_id: String) -> Result<(), String> {
+    let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+    let (division, tracks) = parse_smf(&bytes)?;
+    let (tempo_usec_per_quarter, events) = schedule(&tracks)?;
+
+    let ms_per_tick = tempo_usec_per_quarter as f64 / 1000.0 / division as f64;
+
+    thread::spawn(move || {
+        let start = Instant::now();
+        for event in events {
+            let target_ms = event.tick as f64 * ms_per_tick;
+            let elapsed_ms = start.elapsed().as_secs_f64() * 1000.0;
+            if target_ms > elapsed_ms {
+                thread::sleep(Duration::from_secs_f64((target_ms - elapsed_ms) / 1000.0));
+            }
+
+            if let Ok(mut outputs) = SESSION.outputs.lock() {
+                if let Some(conn) = outputs.get_mut(&output_id) {
+                    let _ = conn.send(&event.data);
+                }
+            }
+        }
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_non_smf_bytes() {
+        assert!(parse_smf(&[0, 1, 2, 3]).is_err());
+    }
+
+    #[test]
+    fn rejects_truncated_track_instead_of_panicking() {
+        let mut bytes = b"MThd".to_vec();
+        bytes.extend_from_slice(&6u32.to_be_bytes());
+        bytes.extend_from_slice(&1u16.to_be_bytes());
+        bytes.extend_from_slice(&1u16.to_be_bytes());
+        bytes.extend_from_slice(&480u16.to_be_bytes());
+        bytes.extend_from_slice(b"MTrk");
+        bytes.extend_from_slice(&100u32.to_be_bytes()); // claims far more data than follows
+        bytes.extend_from_slice(&[0x00, 0x90, 0x40]);
+        assert!(parse_smf(&bytes).is_err());
+    }
+
+    #[test]
+    fn schedule_restores_the_leading_f0_on_sysex_events() {
+        // Delta 0, status F0, VLQ len 3, payload 7E 00 F7 (F0 stripped per SMF framing).
+        let track = vec![0x00, 0xF0, 0x03, 0x7E, 0x00, 0xF7];
+        let (_, events) = schedule(&[track]).unwrap();
+        assert_eq!(events[0].data, vec![0xF0, 0x7E, 0x00, 0xF7]);
+    }
+}
@@ -0,0 +1,168 @@
+use once_cell::sync::Lazy;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone)]
+struct RecordedEvent {
+    timestamp_ms: u128,
+    data: Vec<u8>,
+}
+
+#[derive(Default)]
+struct Recorder {
+    armed: bool,
+    events: Vec<RecordedEvent>,
+}
+
+static RECORDER: Lazy<Mutex<Recorder>> = Lazy::new(|| Mutex::new(Recorder::default()));
+
+/// Arms the recorder, discarding whatever was captured last time.
+pub fn arm() {
+    if let Ok(mut recorder) = RECORDER.lock() {
+        recorder.armed = true;
+        recorder.events.clear();
+    }
+}
+
+pub fn disarm() {
+    if let Ok(mut recorder) = RECORDER.lock() {
+        recorder.armed = false;
+    }
+}
+
+/// Called from every input connection's callback; a no-op unless armed,
+/// so normal playthrough pays no recording overhead.
+pub fn capture(timestamp_ms: u128, data: &[u8]) {
+    if let Ok(mut recorder) = RECORDER.lock() {
+        if recorder.armed {
+            recorder.events.push(RecordedEvent { timestamp_ms, data: data.to_vec() });
+        }
+    }
+}
+
+fn write_vlq(mut value: u32, out: &mut Vec<u8>) {
+    let mut buffer = value & 0x7F;
+    value >>= 7;
+    while value > 0 {
+        buffer <<= 8;
+        buffer |= 0x80 | (value & 0x7F);
+        value >>= 7;
+    }
+    loop {
+        out.push((buffer & 0xFF) as u8);
+        if buffer & 0x80 != 0 {
+            buffer >>= 8;
+        } else {
+            break;
+        }
+    }
+}
+
+/// Channel a raw MIDI message belongs on, or `None` for system messages,
+/// which get their own track.
+fn channel_of(data: &[u8]) -> Option<u8> {
+    data.first().filter(|&&b| b < 0xF0).map(|&b| b & 0x0F)
+}
+
+fn build_track(events: &[(u32, Vec<u8>)], tempo_usec_per_quarter: Option<u32>) -> Vec<u8> {
+    let mut body = Vec::new();
+
+    if let Some(usec) = tempo_usec_per_quarter {
+        write_vlq(0, &mut body);
+        body.extend_from_slice(&[0xFF, 0x51, 0x03, (usec >> 16) as u8, (usec >> 8) as u8, usec as u8]);
+    }
+
+    for (delta, data) in events {
+        write_vlq(*delta, &mut body);
+        if data.first() == Some(&0xF0) {
+            // SMF frames a SysEx event as `F0 <vlq len> <payload...F7>`,
+            // not the raw captured bytes: the length covers everything
+            // after the leading F0, including the trailing F7.
+            body.push(0xF0);
+            write_vlq((data.len() - 1) as u32, &mut body);
+            body.extend_from_slice(&data[1..]);
+        } else {
+            body.extend_from_slice(data);
+        }
+    }
+
+    write_vlq(0, &mut body);
+    body.extend_from_slice(&[0xFF, 0x2F, 0x00]);
+
+    let mut track = Vec::with_capacity(body.len() + 8);
+    track.extend_from_slice(b"MTrk");
+    track.extend_from_slice(&(body.len() as u32).to_be_bytes());
+    track.extend_from_slice(&body);
+    track
+}
+
+/// Exports everything captured since the recorder was last armed as a
+/// Type-1 Standard MIDI File: one track per channel (plus a tempo-only
+/// track 0), delta times in ticks computed from the millisecond
+/// timestamps at the given PPQ and tempo.
+pub fn export_recording(path: &str, ppq: u16, tempo_bpm: f64) -> Result<(), String> {
+    let events = RECORDER.lock().map_err(|_| "Lock poisoned")?.events.clone();
+    let Some(first) = events.first() else {
+        return Err("No recorded events to export".into());
+    };
+
+    let tempo_usec_per_quarter = (60_000_000.0 / tempo_bpm).round() as u32;
+    let ticks_per_ms = (ppq as f64 * tempo_bpm) / 60_000.0;
+    let start_ms = first.timestamp_ms;
+
+    let mut by_channel: BTreeMap<Option<u8>, Vec<(u32, Vec<u8>)>> = BTreeMap::new();
+    let mut last_tick: BTreeMap<Option<u8>, u32> = BTreeMap::new();
+
+    for event in &events {
+        let channel = channel_of(&event.data);
+        let tick = (((event.timestamp_ms - start_ms) as f64) * ticks_per_ms).round() as u32;
+        let previous = last_tick.entry(channel).or_insert(0);
+        let delta = tick.saturating_sub(*previous);
+        *previous = tick;
+        by_channel.entry(channel).or_default().push((delta, event.data.clone()));
+    }
+
+    let mut tracks = vec![build_track(&[], Some(tempo_usec_per_quarter))];
+    for events in by_channel.into_values() {
+        tracks.push(build_track(&events, None));
+    }
+
+    let mut file = Vec::new();
+    file.extend_from_slice(b"MThd");
+    file.extend_from_slice(&6u32.to_be_bytes());
+    file.extend_from_slice(&1u16.to_be_bytes()); // format 1: simultaneous tracks
+    file.extend_from_slice(&(tracks.len() as u16).to_be_bytes());
+    file.extend_from_slice(&ppq.to_be_bytes());
+    for track in tracks {
+        file.extend_from_slice(&track);
+    }
+
+    std::fs::write(path, file).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vlq_encodes_largest_three_byte_value() {
+        let mut out = Vec::new();
+        write_vlq(0x200000 - 1, &mut out); // largest 3-byte VLQ value
+        assert_eq!(out, vec![0xFF, 0xFF, 0x7F]);
+    }
+
+    #[test]
+    fn vlq_encodes_zero_as_single_byte() {
+        let mut out = Vec::new();
+        write_vlq(0, &mut out);
+        assert_eq!(out, vec![0x00]);
+    }
+
+    #[test]
+    fn build_track_frames_sysex_with_a_length_vlq() {
+        let sysex = vec![0xF0, 0x7E, 0x00, 0xF7];
+        let track = build_track(&[(0, sysex)], None);
+        // MTrk header (8 bytes), delta 0x00, then F0 <len=3> <7E 00 F7>.
+        assert_eq!(&track[8..], &[0x00, 0xF0, 0x03, 0x7E, 0x00, 0xF7, 0x00, 0xFF, 0x2F, 0x00]);
+    }
+}
@@ -0,0 +1,239 @@
+use super::parser::MidiEvent;
+use super::session::SESSION;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+/// Message categories a route's allow/deny list can filter on, one per
+/// [`MidiEvent`] variant. Timing clock and active sensing are not told
+/// apart: both are `SystemRealtime`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MessageKind {
+    NoteOn,
+    NoteOff,
+    ControlChange,
+    ProgramChange,
+    PitchBend,
+    ChannelPressure,
+    PolyPressure,
+    SystemRealtime,
+    SystemCommon,
+}
+
+fn kind_of(event: &MidiEvent) -> MessageKind {
+    match event {
+        MidiEvent::NoteOn { .. } => MessageKind::NoteOn,
+        MidiEvent::NoteOff { .. } => MessageKind::NoteOff,
+        MidiEvent::ControlChange { .. } => MessageKind::ControlChange,
+        MidiEvent::ProgramChange { .. } => MessageKind::ProgramChange,
+        MidiEvent::PitchBend { .. } => MessageKind::PitchBend,
+        MidiEvent::ChannelPressure { .. } => MessageKind::ChannelPressure,
+        MidiEvent::PolyPressure { .. } => MessageKind::PolyPressure,
+        MidiEvent::SystemRealtime { .. } => MessageKind::SystemRealtime,
+        MidiEvent::SystemCommon { .. } => MessageKind::SystemCommon,
+    }
+}
+
+/// Per-route transform, applied in order: allow/deny, then channel remap,
+/// then range filters, then velocity scaling.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct RouteTransform {
+    #[serde(default)]
+    pub channel_map: Option<HashMap<u8, u8>>,
+    #[serde(default)]
+    pub note_range: Option<(u8, u8)>,
+    #[serde(default)]
+    pub cc_range: Option<(u8, u8)>,
+    #[serde(default)]
+    pub velocity_scale: Option<f32>,
+    #[serde(default)]
+    pub allow: Option<HashSet<MessageKind>>,
+    #[serde(default)]
+    pub deny: Option<HashSet<MessageKind>>,
+}
+
+/// A thru path from one open input connection to one open output
+/// connection, evaluated inside the input callback so forwarding stays
+/// low-latency.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Route {
+    pub source: String,
+    pub destination: String,
+    #[serde(default)]
+    pub transform: RouteTransform,
+}
+
+pub static ROUTES: Lazy<Mutex<Vec<Route>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+pub fn set_routes(routes: Vec<Route>) {
+    if let Ok(mut current) = ROUTES.lock() {
+        *current = routes;
+    }
+}
+
+/// Forwards `events` from `source` to every route's destination,
+/// transforming and filtering each event along the way.
+pub fn forward(source: &str, events: &[MidiEvent]) {
+    let Ok(routes) = ROUTES.lock() else { return };
+    if routes.is_empty() {
+        return;
+    }
+
+    for route in routes.iter().filter(|r| r.source == source) {
+        let Ok(mut outputs) = SESSION.outputs.lock() else { continue };
+        let Some(conn) = outputs.get_mut(&route.destination) else { continue };
+
+        for event in events {
+            if let Some(transformed) = apply_transform(&route.transform, event) {
+                let _ = conn.send(&encode(&transformed));
+            }
+        }
+    }
+}
+
+fn apply_transform(transform: &RouteTransform, event: &MidiEvent) -> Option<MidiEvent> {
+    let kind = kind_of(event);
+    if let Some(allow) = &transform.allow {
+        if !allow.contains(&kind) {
+            return None;
+        }
+    }
+    if let Some(deny) = &transform.deny {
+        if deny.contains(&kind) {
+            return None;
+        }
+    }
+
+    let mut event = event.clone();
+
+    if let Some(map) = &transform.channel_map {
+        if let Some(channel) = channel_of_mut(&mut event) {
+            if let Some(&mapped) = map.get(channel) {
+                *channel = mapped;
+            }
+        }
+    }
+
+    match &event {
+        MidiEvent::NoteOn { key, .. } | MidiEvent::NoteOff { key, .. } | MidiEvent::PolyPressure { key, .. } => {
+            if let Some((lo, hi)) = transform.note_range {
+                if *key < lo || *key > hi {
+                    return None;
+                }
+            }
+        }
+        MidiEvent::ControlChange { controller, .. } => {
+            if let Some((lo, hi)) = transform.cc_range {
+                if *controller < lo || *controller > hi {
+                    return None;
+                }
+            }
+        }
+        _ => {}
+    }
+
+    if let Some(scale) = transform.velocity_scale {
+        if let MidiEvent::NoteOn { velocity, .. } = &mut event {
+            *velocity = scale_u7(*velocity, scale);
+        }
+    }
+
+    Some(event)
+}
+
+fn channel_of_mut(event: &mut MidiEvent) -> Option<&mut u8> {
+    match event {
+        MidiEvent::NoteOn { channel, .. }
+        | MidiEvent::NoteOff { channel, .. }
+        | MidiEvent::ControlChange { channel, .. }
+        | MidiEvent::ProgramChange { channel, .. }
+        | MidiEvent::PitchBend { channel, .. }
+        | MidiEvent::ChannelPressure { channel, .. }
+        | MidiEvent::PolyPressure { channel, .. } => Some(channel),
+        MidiEvent::SystemRealtime { .. } | MidiEvent::SystemCommon { .. } => None,
+    }
+}
+
+fn scale_u7(value: u8, scale: f32) -> u8 {
+    ((value as f32 * scale).round().clamp(0.0, 127.0)) as u8
+}
+
+fn encode(event: &MidiEvent) -> Vec<u8> {
+    match event {
+        MidiEvent::NoteOn { channel, key, velocity } => vec![0x90 | channel, *key, *velocity],
+        MidiEvent::NoteOff { channel, key, velocity } => vec![0x80 | channel, *key, *velocity],
+        MidiEvent::ControlChange { channel, controller, value } => vec![0xB0 | channel, *controller, *value],
+        MidiEvent::ProgramChange { channel, program } => vec![0xC0 | channel, *program],
+        MidiEvent::PitchBend { channel, value } => {
+            let raw = (*value + 8192) as u16;
+            vec![0xE0 | channel, (raw & 0x7F) as u8, ((raw >> 7) & 0x7F) as u8]
+        }
+        MidiEvent::ChannelPressure { channel, pressure } => vec![0xD0 | channel, *pressure],
+        MidiEvent::PolyPressure { channel, key, pressure } => vec![0xA0 | channel, *key, *pressure],
+        MidiEvent::SystemRealtime { status } => vec![*status],
+        // SysEx (status 0xF0) already stores the full F0..F7 frame in
+        // `data` (see parser.rs), but every other system-common status
+        // (Quarter Frame, Song Position, Song Select, Tune Request) is
+        // stored as data bytes only, so the status byte must be put back
+        // or the re-encoded message is missing it entirely.
+        MidiEvent::SystemCommon { status, data } if *status == 0xF0 => data.clone(),
+        MidiEvent::SystemCommon { status, data } => {
+            let mut bytes = Vec::with_capacity(1 + data.len());
+            bytes.push(*status);
+            bytes.extend_from_slice(data);
+            bytes
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn channel_map_remaps_note_on() {
+        let transform = RouteTransform {
+            channel_map: Some(HashMap::from([(0, 9)])),
+            ..Default::default()
+        };
+        let event = MidiEvent::NoteOn { channel: 0, key: 60, velocity: 100 };
+        let result = apply_transform(&transform, &event).unwrap();
+        assert_eq!(result, MidiEvent::NoteOn { channel: 9, key: 60, velocity: 100 });
+    }
+
+    #[test]
+    fn note_range_filters_out_of_range_keys() {
+        let transform = RouteTransform { note_range: Some((60, 72)), ..Default::default() };
+        let in_range = MidiEvent::NoteOn { channel: 0, key: 60, velocity: 100 };
+        let out_of_range = MidiEvent::NoteOn { channel: 0, key: 59, velocity: 100 };
+        assert!(apply_transform(&transform, &in_range).is_some());
+        assert!(apply_transform(&transform, &out_of_range).is_none());
+    }
+
+    #[test]
+    fn deny_list_blocks_system_realtime() {
+        let transform = RouteTransform {
+            deny: Some(HashSet::from([MessageKind::SystemRealtime])),
+            ..Default::default()
+        };
+        let event = MidiEvent::SystemRealtime { status: 0xF8 };
+        assert!(apply_transform(&transform, &event).is_none());
+    }
+
+    #[test]
+    fn encode_restores_status_byte_for_non_sysex_system_common() {
+        // Song Position Pointer (0xF2) is stored as data-bytes-only, unlike
+        // SysEx which keeps its full F0..F7 frame in `data`.
+        let event = MidiEvent::SystemCommon { status: 0xF2, data: vec![0x10, 0x20] };
+        assert_eq!(encode(&event), vec![0xF2, 0x10, 0x20]);
+    }
+
+    #[test]
+    fn velocity_scale_clamps_to_u7_range() {
+        let transform = RouteTransform { velocity_scale: Some(2.0), ..Default::default() };
+        let event = MidiEvent::NoteOn { channel: 0, key: 60, velocity: 100 };
+        let result = apply_transform(&transform, &event).unwrap();
+        assert_eq!(result, MidiEvent::NoteOn { channel: 0, key: 60, velocity: 127 });
+    }
+}
@@ -0,0 +1,44 @@
+use super::ignore::IgnoreFlags;
+use once_cell::sync::Lazy;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+/// Tracks every MIDI port CueBoard currently has open, keyed by a
+/// caller-supplied connection id instead of a single global slot, so a
+/// control surface, a lighting console and a DAW can all stay connected
+/// at once.
+pub struct Session {
+    pub inputs: Mutex<HashMap<String, midir::MidiInputConnection<()>>>,
+    pub outputs: Mutex<HashMap<String, midir::MidiOutputConnection>>,
+    /// Port name each open connection was opened against. Port indices
+    /// are not stable across a replug, so the hot-plug watcher matches
+    /// on name instead when deciding whether a connection has reappeared.
+    pub input_names: Mutex<HashMap<String, String>>,
+    pub output_names: Mutex<HashMap<String, String>>,
+    /// Ignore flags each input connection was opened with, so the
+    /// hot-plug watcher can reconnect with the same flags the caller
+    /// originally asked for.
+    pub input_ignore: Mutex<HashMap<String, IgnoreFlags>>,
+    /// Connection ids created via `midi_create_virtual_{input,output}`.
+    /// Virtual ports are CueBoard's own endpoint, not a peer device, so
+    /// they never show up in `midi_list_inputs`/`midi_list_outputs` and
+    /// must be excluded from hot-plug monitoring.
+    pub virtual_inputs: Mutex<HashSet<String>>,
+    pub virtual_outputs: Mutex<HashSet<String>>,
+}
+
+impl Session {
+    fn new() -> Self {
+        Self {
+            inputs: Mutex::new(HashMap::new()),
+            outputs: Mutex::new(HashMap::new()),
+            input_names: Mutex::new(HashMap::new()),
+            output_names: Mutex::new(HashMap::new()),
+            input_ignore: Mutex::new(HashMap::new()),
+            virtual_inputs: Mutex::new(HashSet::new()),
+            virtual_outputs: Mutex::new(HashSet::new()),
+        }
+    }
+}
+
+pub static SESSION: Lazy<Session> = Lazy::new(Session::new);
@@ -0,0 +1,60 @@
+use super::session::SESSION;
+#[cfg(not(target_os = "windows"))]
+use midir::os::unix::{VirtualInput, VirtualOutput};
+
+/// midir can create virtual ports on ALSA, JACK and CoreMIDI, but WinMM
+/// and WinRT expose no such API, so the Windows build of this function is
+/// a stub that reports the platform as unsupported instead of failing to
+/// compile against a method that doesn't exist there.
+#[cfg(not(target_os = "windows"))]
+pub fn create_virtual_input(app: tauri::AppHandle, connection_id: String, name: String) -> Result<(), String> {
+    let mut midi_in = midir::MidiInput::new("wc-midi-in").map_err(|e| e.to_string())?;
+    midi_in.ignore(midir::Ignore::None);
+
+    {
+        let mut inputs = SESSION.inputs.lock().map_err(|_| "Lock poisoned")?;
+        inputs.remove(&connection_id);
+    }
+
+    let conn = midi_in
+        .create_virtual(&name, super::input_callback(app, connection_id.clone()), ())
+        .map_err(|e| e.to_string())?;
+
+    let mut inputs = SESSION.inputs.lock().map_err(|_| "Lock poisoned")?;
+    inputs.insert(connection_id.clone(), conn);
+    let mut names = SESSION.input_names.lock().map_err(|_| "Lock poisoned")?;
+    names.insert(connection_id.clone(), name);
+    let mut virtual_inputs = SESSION.virtual_inputs.lock().map_err(|_| "Lock poisoned")?;
+    virtual_inputs.insert(connection_id);
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+pub fn create_virtual_input(_app: tauri::AppHandle, _connection_id: String, _name: String) -> Result<(), String> {
+    Err("Virtual MIDI ports are not supported on Windows".into())
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn create_virtual_output(connection_id: String, name: String) -> Result<(), String> {
+    let midi_out = midir::MidiOutput::new("wc-midi-out").map_err(|e| e.to_string())?;
+
+    {
+        let mut outputs = SESSION.outputs.lock().map_err(|_| "Lock poisoned")?;
+        outputs.remove(&connection_id);
+    }
+
+    let conn = midi_out.create_virtual(&name).map_err(|e| e.to_string())?;
+
+    let mut outputs = SESSION.outputs.lock().map_err(|_| "Lock poisoned")?;
+    outputs.insert(connection_id.clone(), conn);
+    let mut names = SESSION.output_names.lock().map_err(|_| "Lock poisoned")?;
+    names.insert(connection_id.clone(), name);
+    let mut virtual_outputs = SESSION.virtual_outputs.lock().map_err(|_| "Lock poisoned")?;
+    virtual_outputs.insert(connection_id);
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+pub fn create_virtual_output(_connection_id: String, _name: String) -> Result<(), String> {
+    Err("Virtual MIDI ports are not supported on Windows".into())
+}
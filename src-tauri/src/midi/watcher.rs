@@ -0,0 +1,165 @@
+use super::{midi_list_inputs, midi_list_outputs, open_input, open_output, session::SESSION, MidiDeviceInfo};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::thread;
+use std::time::Duration;
+use tauri::Emitter;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(1500);
+
+#[derive(Serialize, Debug)]
+struct DevicesChangedPayload {
+    added: Vec<MidiDeviceInfo>,
+    removed: Vec<MidiDeviceInfo>,
+}
+
+#[derive(Serialize, Debug)]
+struct ConnectionLostPayload {
+    connection_id: String,
+}
+
+/// Polls `midi_list_inputs`/`midi_list_outputs` on a short interval since
+/// midir gives no native connect/disconnect callback on this platform
+/// (unlike CoreMIDI/WinRT), diffs the result against the previous
+/// snapshot, and emits `midi://devices-changed`. Open connections whose
+/// port disappears get a `midi://connection-lost` event and are
+/// automatically reconnected by name if a matching device reappears.
+pub fn start(app: tauri::AppHandle) {
+    thread::spawn(move || {
+        let mut known_inputs = midi_list_inputs().unwrap_or_default();
+        let mut known_outputs = midi_list_outputs().unwrap_or_default();
+        // Tracked separately per direction: an input and an output can
+        // share a connection id, and losing one must not be confused for
+        // the other reappearing.
+        let mut lost_inputs: HashSet<String> = HashSet::new();
+        let mut lost_outputs: HashSet<String> = HashSet::new();
+
+        loop {
+            thread::sleep(POLL_INTERVAL);
+
+            let current_inputs = match midi_list_inputs() {
+                Ok(v) => v,
+                Err(_) => continue,
+            };
+            let current_outputs = match midi_list_outputs() {
+                Ok(v) => v,
+                Err(_) => continue,
+            };
+
+            let (added_in, removed_in) = diff(&known_inputs, &current_inputs);
+            let (added_out, removed_out) = diff(&known_outputs, &current_outputs);
+
+            if !added_in.is_empty() || !removed_in.is_empty() || !added_out.is_empty() || !removed_out.is_empty() {
+                let payload = DevicesChangedPayload {
+                    added: added_in.into_iter().chain(added_out).collect(),
+                    removed: removed_in.into_iter().chain(removed_out).collect(),
+                };
+                let _ = app.emit("midi://devices-changed", &payload);
+            }
+
+            handle_lost_and_reconnect(&app, &current_inputs, &current_outputs, &mut lost_inputs, &mut lost_outputs);
+
+            known_inputs = current_inputs;
+            known_outputs = current_outputs;
+        }
+    });
+}
+
+/// Keys each device on `(name, occurrence-index)` rather than name alone,
+/// so two identical controllers sharing a port name (common with
+/// duplicate USB devices) don't collapse into a single entry: unplugging
+/// one still leaves the other's key matched in the other snapshot.
+fn occurrence_keys(devices: &[MidiDeviceInfo]) -> HashSet<(&str, usize)> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    devices
+        .iter()
+        .map(|d| {
+            let count = counts.entry(d.name.as_str()).or_insert(0);
+            let key = (d.name.as_str(), *count);
+            *count += 1;
+            key
+        })
+        .collect()
+}
+
+fn diff(previous: &[MidiDeviceInfo], current: &[MidiDeviceInfo]) -> (Vec<MidiDeviceInfo>, Vec<MidiDeviceInfo>) {
+    let prev_keys = occurrence_keys(previous);
+    let cur_keys = occurrence_keys(current);
+
+    let mut added_counts: HashMap<&str, usize> = HashMap::new();
+    let added = current
+        .iter()
+        .filter(|d| {
+            let count = added_counts.entry(d.name.as_str()).or_insert(0);
+            let key = (d.name.as_str(), *count);
+            *count += 1;
+            !prev_keys.contains(&key)
+        })
+        .cloned()
+        .collect();
+
+    let mut removed_counts: HashMap<&str, usize> = HashMap::new();
+    let removed = previous
+        .iter()
+        .filter(|d| {
+            let count = removed_counts.entry(d.name.as_str()).or_insert(0);
+            let key = (d.name.as_str(), *count);
+            *count += 1;
+            !cur_keys.contains(&key)
+        })
+        .cloned()
+        .collect();
+
+    (added, removed)
+}
+
+fn handle_lost_and_reconnect(
+    app: &tauri::AppHandle,
+    current_inputs: &[MidiDeviceInfo],
+    current_outputs: &[MidiDeviceInfo],
+    lost_inputs: &mut HashSet<String>,
+    lost_outputs: &mut HashSet<String>,
+) {
+    let input_names = SESSION.input_names.lock().map(|g| g.clone()).unwrap_or_default();
+    let virtual_inputs = SESSION.virtual_inputs.lock().map(|g| g.clone()).unwrap_or_default();
+    for (id, name) in input_names.iter() {
+        // Virtual ports are CueBoard's own endpoint, not a peer device we
+        // polled for, so they never appear in `current_inputs` and must
+        // not be treated as lost.
+        if virtual_inputs.contains(id) {
+            continue;
+        }
+        match current_inputs.iter().find(|d| &d.name == name) {
+            Some(device) if lost_inputs.remove(id) => {
+                let ignore = SESSION
+                    .input_ignore
+                    .lock()
+                    .ok()
+                    .and_then(|g| g.get(id).copied())
+                    .unwrap_or_default();
+                let _ = open_input(app.clone(), id.clone(), device.id, ignore);
+            }
+            None if lost_inputs.insert(id.clone()) => {
+                let _ = app.emit("midi://connection-lost", &ConnectionLostPayload { connection_id: id.clone() });
+            }
+            _ => {}
+        }
+    }
+
+    let output_names = SESSION.output_names.lock().map(|g| g.clone()).unwrap_or_default();
+    let virtual_outputs = SESSION.virtual_outputs.lock().map(|g| g.clone()).unwrap_or_default();
+    for (id, name) in output_names.iter() {
+        if virtual_outputs.contains(id) {
+            continue;
+        }
+        match current_outputs.iter().find(|d| &d.name == name) {
+            Some(device) if lost_outputs.remove(id) => {
+                let _ = open_output(id.clone(), device.id);
+            }
+            None if lost_outputs.insert(id.clone()) => {
+                let _ = app.emit("midi://connection-lost", &ConnectionLostPayload { connection_id: id.clone() });
+            }
+            _ => {}
+        }
+    }
+}